@@ -11,8 +11,24 @@
 //! println!("{}", progress.get_progress_string()); // job name 2/100 - 2.0% started 2s ago, eta: 98s
 //! ```
 
+use std::collections::VecDeque;
 use std::time::{Duration, Instant};
-use tracing::warn;
+use tracing::{info, warn};
+
+/// Number of recent throughput samples kept to estimate the ETA.
+const SAMPLE_WINDOW: usize = 15;
+/// Factor each older sample's weight is multiplied by relative to the one after it.
+const SAMPLE_DECAY: f64 = 0.75;
+/// Default minimum elapsed time before we'll report a concrete ETA.
+const DEFAULT_WARMUP_DURATION: Duration = Duration::from_secs(3);
+/// Default minimum fraction of work done before we'll report a concrete ETA.
+const DEFAULT_WARMUP_FRACTION: f64 = 0.05;
+/// Default minimum time between `maybe_log` log lines.
+const DEFAULT_LOG_INTERVAL: Duration = Duration::from_secs(5);
+/// Default minimum job duration before `maybe_log` will log anything at all.
+const DEFAULT_MIN_DURATION_TO_LOG: Duration = Duration::from_secs(1);
+/// Weight given to each new cost sample in `WorkLimiter`'s EWMA.
+const COST_EWMA_ALPHA: f64 = 0.25;
 
 /// The struct holding the state and functions related to our progress.
 pub struct Progress {
@@ -20,6 +36,26 @@ pub struct Progress {
     pub work_done: u64,
     started_at: Instant,
     work_total: u64,
+    /// `elapsed()` as of the last time `work_done` was advanced.
+    last_elapsed: Duration,
+    /// What `work_done` was at `last_elapsed`, used to compute step deltas.
+    last_work_done: u64,
+    /// Recent `(steps_delta, seconds_delta)` throughput samples, oldest first.
+    samples: VecDeque<(u64, f64)>,
+    /// Minimum elapsed time before a concrete ETA is reported.
+    warmup_duration: Duration,
+    /// Minimum fraction of work done before a concrete ETA is reported.
+    warmup_fraction: f64,
+    /// Minimum time between `maybe_log` log lines.
+    log_interval: Duration,
+    /// Minimum job duration before `maybe_log` will log anything at all.
+    min_duration_to_log: Duration,
+    /// When `maybe_log` last emitted a log line.
+    last_logged_at: Option<Instant>,
+    /// Total time spent paused so far, not counting a pause in progress.
+    paused_duration: Duration,
+    /// When the current pause started, if we're paused.
+    paused_at: Option<Instant>,
 }
 
 impl Progress {
@@ -37,12 +73,170 @@ impl Progress {
     /// let mut progress = Progress::new("my job", 100);
     /// ```
     pub fn new(name: &str, work_todo: u64) -> Self {
+        Self::with_warmup(name, work_todo, DEFAULT_WARMUP_DURATION, DEFAULT_WARMUP_FRACTION)
+    }
+
+    /// Makes a progress object like [`Progress::new`], but with a custom
+    /// warmup: `get_progress_string` withholds a concrete ETA until either
+    /// `warmup_duration` has elapsed or `warmup_fraction` of the work is
+    /// done, whichever comes first. Throughput right after starting is too
+    /// noisy to trust, so without a warmup the first couple of seconds tend
+    /// to produce absurd estimates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use pit_wall::Progress;
+    /// let mut progress = Progress::with_warmup("my job", 100, Duration::from_secs(1), 0.1);
+    /// ```
+    pub fn with_warmup(
+        name: &str,
+        work_todo: u64,
+        warmup_duration: Duration,
+        warmup_fraction: f64,
+    ) -> Self {
+        let now = Instant::now();
         Self {
             name: name.to_owned(),
-            started_at: Instant::now(),
+            started_at: now,
             work_done: 0,
             work_total: work_todo,
+            last_elapsed: Duration::ZERO,
+            last_work_done: 0,
+            samples: VecDeque::with_capacity(SAMPLE_WINDOW),
+            warmup_duration,
+            warmup_fraction,
+            log_interval: DEFAULT_LOG_INTERVAL,
+            min_duration_to_log: DEFAULT_MIN_DURATION_TO_LOG,
+            last_logged_at: None,
+            paused_duration: Duration::ZERO,
+            paused_at: None,
+        }
+    }
+
+    /// Override how often `maybe_log` may emit a log line, in place of the
+    /// default 5 second interval.
+    pub fn set_log_interval(&mut self, interval: Duration) {
+        self.log_interval = interval;
+    }
+
+    /// Override the minimum job duration under which `maybe_log` stays
+    /// silent entirely, in place of the default 1 second.
+    pub fn set_min_duration_to_log(&mut self, min_duration: Duration) {
+        self.min_duration_to_log = min_duration;
+    }
+
+    /// Pause the clock used for elapsed time and ETA calculations. Call this
+    /// before blocking on something external (rate limits, backpressure)
+    /// so the idle time doesn't inflate elapsed time or skew the ETA. A
+    /// no-op if already paused.
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+        }
+    }
+
+    /// Resume the clock after a `pause`. A no-op if not currently paused.
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_duration += Instant::now().duration_since(paused_at);
+        }
+    }
+
+    /// Reset the timing baseline to now, leaving `work_done` untouched.
+    /// Useful to exclude a warmup/setup phase with different cost
+    /// characteristics (e.g. priming a cache) from the elapsed-time and ETA
+    /// figures - call this once priming is done and the subsequent ETA
+    /// will reflect only steady-state throughput. Clears accumulated
+    /// throughput samples and pause state so the first post-reset sample
+    /// starts fresh.
+    pub fn reset_elapsed(&mut self) {
+        self.started_at = Instant::now();
+        self.last_elapsed = Duration::ZERO;
+        self.last_work_done = self.work_done;
+        self.samples.clear();
+        self.paused_duration = Duration::ZERO;
+        self.paused_at = None;
+    }
+
+    /// Wall-clock time elapsed since the job started, excluding any time
+    /// spent paused.
+    pub fn elapsed(&self) -> Duration {
+        let now = Instant::now();
+        let paused_duration = self.paused_duration
+            + match self.paused_at {
+                Some(paused_at) => now.duration_since(paused_at),
+                None => Duration::ZERO,
+            };
+        now.duration_since(self.started_at)
+            .saturating_sub(paused_duration)
+    }
+
+    /// Fraction of work done, from `0.0` to (beyond, if overshot) `1.0`.
+    pub fn fraction(&self) -> f64 {
+        self.work_done as f64 / self.work_total as f64
+    }
+
+    /// Whether `work_done` has reached `work_total`.
+    pub fn is_finished(&self) -> bool {
+        self.work_done >= self.work_total
+    }
+
+    /// Current throughput in units/second, as a recent-weighted average
+    /// rather than the overall average since start. `0.0` if there isn't
+    /// enough sample data yet.
+    pub fn per_sec(&self) -> f64 {
+        self.steps_per_second().unwrap_or(0.0)
+    }
+
+    /// The current ETA estimate, or `None` if it's too early to tell.
+    pub fn eta(&self) -> Option<Duration> {
+        self.estimate_time_left()
+    }
+
+    /// An exponentially-weighted moving average of recent throughput, in
+    /// units/second. `None` if there aren't enough samples yet.
+    fn steps_per_second(&self) -> Option<f64> {
+        let mut weight = 1.0;
+        let mut weighted_steps = 0.0;
+        let mut weighted_time = 0.0;
+        for &(steps, secs) in self.samples.iter().rev() {
+            weighted_steps += steps as f64 * weight;
+            weighted_time += secs * weight;
+            weight *= SAMPLE_DECAY;
+        }
+
+        if weighted_time == 0.0 {
+            return None;
+        }
+
+        let steps_per_second = weighted_steps / weighted_time;
+        if steps_per_second == 0.0 {
+            None
+        } else {
+            Some(steps_per_second)
+        }
+    }
+
+    /// Record a throughput sample for the gap since `last_elapsed`, then
+    /// roll `last_elapsed`/`last_work_done` forward. Uses `elapsed()`
+    /// rather than raw wall-clock time so time spent paused isn't counted
+    /// as throughput-killing idle time.
+    fn record_progress(&mut self) {
+        let elapsed = self.elapsed();
+        let steps_delta = self.work_done.saturating_sub(self.last_work_done);
+        let seconds_delta = elapsed.saturating_sub(self.last_elapsed).as_secs_f64();
+
+        if steps_delta > 0 && seconds_delta > 0.0 {
+            if self.samples.len() == SAMPLE_WINDOW {
+                self.samples.pop_front();
+            }
+            self.samples.push_back((steps_delta, seconds_delta));
         }
+
+        self.last_elapsed = elapsed;
+        self.last_work_done = self.work_done;
     }
 
     /// Increment work done by one unit.
@@ -55,44 +249,60 @@ impl Progress {
     /// ```
     pub fn inc_work_done(&mut self) {
         self.work_done = self.work_done + 1;
+        self.record_progress();
     }
 
     /// Increment work done by a given amonut.
     pub fn inc_work_done_by(&mut self, units: u64) {
         self.work_done = self.work_done + units;
+        self.record_progress();
     }
 
     /// Set work done.
     pub fn set_work_done(&mut self, units: u64) {
         self.work_done = units;
+        self.record_progress();
     }
 
-    /// Get an estimate in seconds of the estimated seconds remaining.
-    /// Uses basic linear interpolation to come up with an estimate.
-    fn estimate_time_left(&self) -> Duration {
+    /// Get an estimate of the time remaining, or `None` if it's too early to
+    /// tell.
+    ///
+    /// Uses an exponentially-weighted moving average over recent throughput
+    /// samples rather than the overall average since start, so a slow start
+    /// or a slow tail doesn't skew the estimate for the whole run. Returns
+    /// `None` while still warming up, or when there aren't enough samples
+    /// yet to say anything useful.
+    fn estimate_time_left(&self) -> Option<Duration> {
         if self.work_done > self.work_total {
             warn!(self.work_done, self.work_total, "work done is larger than work total, using work done == work total to calculate time left");
         }
+
+        if self.elapsed() < self.warmup_duration && self.fraction() < self.warmup_fraction {
+            return None;
+        }
+
         let work_not_done = self
             .work_total
             .checked_sub(self.work_done)
             .unwrap_or(self.work_total);
-        let not_done_to_done_ratio = work_not_done as f64 / self.work_done as f64;
-        let seconds_since_start = Instant::now() - self.started_at;
-        let eta_seconds = not_done_to_done_ratio * seconds_since_start.as_secs() as f64;
+        let steps_per_second = self.steps_per_second()?;
 
-        Duration::from_secs(eta_seconds as u64)
+        let eta_secs = work_not_done as f64 / steps_per_second;
+        Some(Duration::try_from_secs_f64(eta_secs).unwrap_or(Duration::MAX))
     }
 
     /// Returns a formatted string giving a bunch of information on the current progress.
     /// You may want to log this periodically with whatever logging you have set up.
     pub fn get_progress_string(&self) -> String {
-        let time_elapsed = format!("{:.0?}", Instant::now().duration_since(self.started_at));
+        let time_elapsed = format!("{:.0?}", self.elapsed());
 
-        let eta = if self.work_done == self.work_total {
+        let eta = if self.is_finished() {
             "done!".to_string()
         } else {
-            humantime::format_duration(self.estimate_time_left()).to_string()
+            match self.estimate_time_left() {
+                Some(estimate) => humantime::format_duration(estimate).to_string(),
+                None => "estimating…".to_string(),
+            }
         };
 
         format!(
@@ -100,11 +310,153 @@ impl Progress {
             self.name,
             self.work_done,
             self.work_total,
-            self.work_done as f64 / self.work_total as f64 * 100f64,
+            self.fraction() * 100f64,
             time_elapsed,
             eta
         )
     }
+
+    /// Emit a structured `tracing` event describing the current progress,
+    /// and record that we just logged.
+    fn log_progress(&mut self) {
+        let percent = self.fraction() * 100f64;
+        let eta_secs = self.estimate_time_left().map(|d| d.as_secs_f64());
+
+        info!(
+            name = %self.name,
+            work_done = self.work_done,
+            work_total = self.work_total,
+            percent,
+            eta_secs,
+            "progress"
+        );
+
+        self.last_logged_at = Some(Instant::now());
+    }
+
+    /// Log progress through `tracing`, but no more than once per
+    /// `log_interval` and never for jobs that finish inside
+    /// `min_duration_to_log` - call this as often as you like (e.g. every
+    /// loop iteration) and it'll throttle itself.
+    ///
+    /// Once `work_done` reaches `work_total`, every call logs regardless of
+    /// `log_interval`, so the final line is never throttled away, as long as
+    /// the job ran past `min_duration_to_log`.
+    pub fn maybe_log(&mut self) {
+        if self.elapsed() < self.min_duration_to_log {
+            return;
+        }
+
+        let interval_elapsed = match self.last_logged_at {
+            None => true,
+            Some(last_logged_at) => Instant::now().duration_since(last_logged_at) >= self.log_interval,
+        };
+
+        if self.is_finished() || interval_elapsed {
+            self.log_progress();
+        }
+    }
+}
+
+/// Formats a rate as a human-readable string, e.g. `2.1k` for `2143.0`.
+fn format_rate(rate: f64) -> String {
+    if rate >= 1_000_000.0 {
+        format!("{:.1}M", rate / 1_000_000.0)
+    } else if rate >= 1_000.0 {
+        format!("{:.1}k", rate / 1_000.0)
+    } else {
+        format!("{:.1}", rate)
+    }
+}
+
+impl std::fmt::Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}/{} ({:.1}%, {}/s)",
+            self.name,
+            self.work_done,
+            self.work_total,
+            self.fraction() * 100f64,
+            format_rate(self.per_sec())
+        )
+    }
+}
+
+/// Tells a batch loop how many units of work to process before yielding, so
+/// each batch stays close to a target wall-clock window without the caller
+/// hand-tuning a fixed chunk size. Tracks an EWMA of the per-unit cost and
+/// recommends `next_batch = target_window / cost_per_unit`, clamped to a
+/// `min_batch..=max_batch` range. Pairs naturally with
+/// `Progress::inc_work_done_by`.
+pub struct WorkLimiter {
+    target_window: Duration,
+    cost_per_unit: f64,
+    min_batch: u64,
+    max_batch: u64,
+}
+
+impl WorkLimiter {
+    /// Makes a work limiter targeting `target_window` per batch.
+    /// `initial_batch_size` is used to seed the cost estimate before any
+    /// real timings come in; `min_batch` and `max_batch` bound every
+    /// recommendation `next_batch` makes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_batch > max_batch`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use pit_wall::WorkLimiter;
+    /// let limiter = WorkLimiter::new(Duration::from_millis(100), 64, 1, 10_000);
+    /// ```
+    pub fn new(
+        target_window: Duration,
+        initial_batch_size: u64,
+        min_batch: u64,
+        max_batch: u64,
+    ) -> Self {
+        assert!(
+            min_batch <= max_batch,
+            "min_batch ({min_batch}) must not be greater than max_batch ({max_batch})"
+        );
+        let initial_batch_size = initial_batch_size.max(1);
+        Self {
+            target_window,
+            cost_per_unit: target_window.as_secs_f64() / initial_batch_size as f64,
+            min_batch,
+            max_batch,
+        }
+    }
+
+    /// How many units of work the next batch should process to stay close
+    /// to `target_window`.
+    pub fn next_batch(&self) -> u64 {
+        if self.cost_per_unit <= 0.0 {
+            return self.max_batch;
+        }
+        let raw_batch = self.target_window.as_secs_f64() / self.cost_per_unit;
+        // `min`/`max` rather than `clamp` so a reversed bound (which `new`
+        // already rejects, but this stays cheap insurance) saturates
+        // instead of panicking.
+        (raw_batch.round() as u64)
+            .max(self.min_batch)
+            .min(self.max_batch)
+    }
+
+    /// Feed back how long the last batch actually took, updating the
+    /// per-unit cost estimate as an EWMA so `next_batch` adapts over time.
+    pub fn record(&mut self, units_done: u64, elapsed: Duration) {
+        if units_done == 0 {
+            return;
+        }
+        let sample_cost = elapsed.as_secs_f64() / units_done as f64;
+        self.cost_per_unit =
+            COST_EWMA_ALPHA * sample_cost + (1.0 - COST_EWMA_ALPHA) * self.cost_per_unit;
+    }
 }
 
 #[cfg(test)]
@@ -137,23 +489,91 @@ mod tests {
     #[test]
     fn estimate_eta_test() {
         let mut progress = Progress::new("test progress", 100);
-        progress.set_work_done(50);
-        thread::sleep(Duration::from_secs(1));
+        progress.work_done = 50;
+        progress.samples.push_back((50, 10.0));
         let eta = progress.estimate_time_left();
-        assert_eq!(eta, Duration::from_secs(1));
+        // 50 remaining units, at 50 units / 10s = 5 units/s -> 10s left.
+        assert_eq!(eta, Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn estimate_eta_weighs_recent_samples_more_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.work_done = 50;
+        // A slow start followed by a much faster recent pace should pull the
+        // estimate towards the faster, more recent throughput.
+        progress.samples.push_back((10, 10.0));
+        progress.samples.push_back((40, 2.0));
+        let slow_start_eta = progress.estimate_time_left();
+
+        let mut steady_progress = Progress::new("test progress", 100);
+        steady_progress.work_done = 50;
+        steady_progress.samples.push_back((25, 6.0));
+        steady_progress.samples.push_back((25, 6.0));
+        let steady_eta = steady_progress.estimate_time_left();
+
+        assert!(slow_start_eta < steady_eta);
+    }
+
+    #[test]
+    fn estimate_eta_unknown_without_samples_test() {
+        // No warmup, so `None` can only come from the empty sample buffer.
+        let progress = Progress::with_warmup("test progress", 100, Duration::ZERO, 0.0);
+        assert_eq!(progress.estimate_time_left(), None);
+    }
+
+    #[test]
+    fn estimate_eta_saturates_instead_of_panicking_on_overflow_test() {
+        let mut progress = Progress::with_warmup("test progress", u64::MAX, Duration::ZERO, 0.0);
+        // A single, very slow sample (1 unit per hour) against a huge
+        // amount of remaining work pushes the naive ETA far past what
+        // `Duration` can represent.
+        progress.samples.push_back((1, 3600.0));
+        assert_eq!(progress.estimate_time_left(), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn warmup_suppresses_eta_before_threshold_test() {
+        let mut progress = Progress::with_warmup("test progress", 1000, Duration::from_secs(100), 0.5);
+        // 0.1% done, far short of the 50% fraction threshold, and well
+        // under the 100s duration threshold.
+        progress.work_done = 1;
+        progress.samples.push_back((1, 0.001));
+        assert_eq!(progress.estimate_time_left(), None);
+    }
+
+    #[test]
+    fn warmup_lifts_once_fraction_threshold_reached_test() {
+        let mut progress = Progress::with_warmup("test progress", 100, Duration::from_secs(100), 0.05);
+        // 10% done clears the 5% fraction threshold, even though we're
+        // nowhere near the 100s duration threshold.
+        progress.work_done = 10;
+        progress.samples.push_back((10, 2.0));
+        // 90 remaining units, at 10 units / 2s = 5 units/s -> 18s left.
+        assert_eq!(progress.estimate_time_left(), Some(Duration::from_secs(18)));
     }
 
     #[test]
     fn get_progress_string_test() {
         let mut progress = Progress::new("test progress", 100);
-        progress.set_work_done(50);
+        progress.work_done = 50;
+        progress.samples.push_back((50, 10.0));
 
-        // something like `test progress 50/100 - 50.0% started 41ns ago, eta: 0ns`
+        // something like `test progress 50/100 - 50.0% started 41ns ago, eta: 10s`
         // time elapsed will differ from test to test so we skip testing.
         let progress_string = progress.get_progress_string();
 
         assert!(progress_string.starts_with("test progress 50/100 - 50.0% started"));
-        assert!(progress_string.ends_with("ago, eta: 0s"));
+        assert!(progress_string.ends_with("ago, eta: 10s"));
+    }
+
+    #[test]
+    fn get_progress_string_during_warmup_test() {
+        let progress = Progress::new("test progress", 100);
+
+        let progress_string = progress.get_progress_string();
+
+        assert!(progress_string.ends_with("ago, eta: estimating…"));
     }
 
     #[test]
@@ -177,4 +597,218 @@ mod tests {
         progress.inc_work_done();
         progress.estimate_time_left();
     }
+
+    #[test]
+    fn maybe_log_stays_silent_under_min_duration_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.set_min_duration_to_log(Duration::from_secs(3600));
+        progress.set_work_done(100);
+        progress.maybe_log();
+        assert_eq!(progress.last_logged_at, None);
+    }
+
+    #[test]
+    fn maybe_log_throttles_to_the_configured_interval_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.set_min_duration_to_log(Duration::ZERO);
+        progress.set_log_interval(Duration::from_secs(3600));
+
+        progress.maybe_log();
+        let first_logged_at = progress.last_logged_at;
+        assert!(first_logged_at.is_some());
+
+        progress.set_work_done(50);
+        progress.maybe_log();
+        assert_eq!(progress.last_logged_at, first_logged_at);
+    }
+
+    #[test]
+    fn maybe_log_always_logs_on_completion_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.set_min_duration_to_log(Duration::ZERO);
+        progress.set_log_interval(Duration::from_secs(3600));
+
+        progress.maybe_log();
+        let first_logged_at = progress.last_logged_at;
+
+        progress.set_work_done(100);
+        progress.maybe_log();
+        assert!(progress.last_logged_at > first_logged_at);
+    }
+
+    #[test]
+    fn pause_excludes_idle_time_from_elapsed_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.pause();
+        thread::sleep(Duration::from_millis(50));
+        progress.resume();
+
+        assert!(progress.elapsed() < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn resume_without_pause_is_a_no_op_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.resume();
+        assert_eq!(progress.paused_duration, Duration::ZERO);
+    }
+
+    #[test]
+    fn pause_while_already_paused_is_a_no_op_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.pause();
+        let first_paused_at = progress.paused_at;
+        progress.pause();
+        assert_eq!(progress.paused_at, first_paused_at);
+    }
+
+    #[test]
+    fn samples_recorded_across_a_pause_exclude_the_paused_time_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.pause();
+        thread::sleep(Duration::from_millis(50));
+        progress.resume();
+        thread::sleep(Duration::from_millis(5));
+        progress.inc_work_done_by(10);
+
+        let &(_, seconds_delta) = progress.samples.back().unwrap();
+        // The real (unpaused) gap was ~5ms; if the 50ms pause leaked in,
+        // this would be ~55ms instead.
+        assert!(seconds_delta < 0.05);
+    }
+
+    #[test]
+    fn increments_feed_the_estimator_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.inc_work_done_by(50);
+        thread::sleep(Duration::from_millis(10));
+        progress.inc_work_done_by(10);
+
+        assert_eq!(progress.samples.len(), 2);
+    }
+
+    #[test]
+    fn work_limiter_starts_from_initial_batch_size_test() {
+        let limiter = WorkLimiter::new(Duration::from_millis(100), 50, 1, 1000);
+        assert_eq!(limiter.next_batch(), 50);
+    }
+
+    #[test]
+    #[should_panic(expected = "min_batch")]
+    fn work_limiter_rejects_min_greater_than_max_test() {
+        WorkLimiter::new(Duration::from_millis(100), 50, 1000, 1);
+    }
+
+    #[test]
+    fn work_limiter_shrinks_batch_when_cost_rises_test() {
+        let mut limiter = WorkLimiter::new(Duration::from_millis(100), 50, 1, 1000);
+        // 50 units took 200ms, twice the target window, so the next batch
+        // should be recommended smaller.
+        limiter.record(50, Duration::from_millis(200));
+        assert!(limiter.next_batch() < 50);
+    }
+
+    #[test]
+    fn work_limiter_clamps_to_bounds_test() {
+        let mut limiter = WorkLimiter::new(Duration::from_millis(100), 50, 10, 20);
+        // 50 units took 1ms total, so the unclamped recommendation would be
+        // far above max_batch.
+        limiter.record(50, Duration::from_millis(1));
+        assert_eq!(limiter.next_batch(), 20);
+    }
+
+    #[test]
+    fn fraction_test() {
+        let mut progress = Progress::new("test progress", 200);
+        progress.set_work_done(50);
+        assert_eq!(progress.fraction(), 0.25);
+    }
+
+    #[test]
+    fn is_finished_test() {
+        let mut progress = Progress::new("test progress", 100);
+        assert!(!progress.is_finished());
+        progress.set_work_done(100);
+        assert!(progress.is_finished());
+    }
+
+    #[test]
+    fn per_sec_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.work_done = 50;
+        progress.samples.push_back((50, 10.0));
+        assert_eq!(progress.per_sec(), 5.0);
+    }
+
+    #[test]
+    fn per_sec_without_samples_is_zero_test() {
+        let progress = Progress::new("test progress", 100);
+        assert_eq!(progress.per_sec(), 0.0);
+    }
+
+    #[test]
+    fn eta_matches_estimate_time_left_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.work_done = 50;
+        progress.samples.push_back((50, 10.0));
+        assert_eq!(progress.eta(), Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn display_includes_name_counts_and_rate_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.work_done = 50;
+        progress.samples.push_back((50, 10.0));
+
+        assert_eq!(
+            progress.to_string(),
+            "test progress 50/100 (50.0%, 5.0/s)"
+        );
+    }
+
+    #[test]
+    fn format_rate_uses_k_and_m_suffixes_test() {
+        assert_eq!(format_rate(42.0), "42.0");
+        assert_eq!(format_rate(2_143.0), "2.1k");
+        assert_eq!(format_rate(3_500_000.0), "3.5M");
+    }
+
+    #[test]
+    fn reset_elapsed_keeps_work_done_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.set_work_done(50);
+        progress.reset_elapsed();
+        assert_eq!(progress.work_done, 50);
+    }
+
+    #[test]
+    fn reset_elapsed_clears_samples_and_pause_state_test() {
+        let mut progress = Progress::new("test progress", 100);
+        progress.work_done = 50;
+        progress.samples.push_back((50, 10.0));
+        progress.pause();
+
+        progress.reset_elapsed();
+
+        assert!(progress.samples.is_empty());
+        assert_eq!(progress.paused_at, None);
+        assert_eq!(progress.paused_duration, Duration::ZERO);
+    }
+
+    #[test]
+    fn reset_elapsed_restarts_the_elapsed_clock_test() {
+        let mut progress = Progress::new("test progress", 100);
+        thread::sleep(Duration::from_millis(20));
+
+        progress.reset_elapsed();
+
+        assert!(progress.elapsed() < Duration::from_millis(20));
+    }
+
+    #[test]
+    fn work_limiter_ignores_empty_batches_test() {
+        let mut limiter = WorkLimiter::new(Duration::from_millis(100), 50, 1, 1000);
+        limiter.record(0, Duration::from_secs(10));
+        assert_eq!(limiter.next_batch(), 50);
+    }
 }